@@ -1,8 +1,9 @@
 //! A very mediocre hashmap
 
 use std::{
-    hash::{DefaultHasher, Hash, Hasher},
-    iter,
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
 };
 
 const TARGET_LOAD_FACTOR: f64 = 0.7;
@@ -10,6 +11,16 @@ const TARGET_LOAD_FACTOR: f64 = 0.7;
 #[cfg(test)]
 mod test;
 
+/// A single slot in the flat open-addressing store.
+///
+/// The full 64-bit hash is cached alongside the entry so that resizing and
+/// probe comparisons never have to re-hash the key.
+#[derive(Debug, Clone)]
+enum Slot<K, V> {
+    Empty,
+    Full { hash: u64, key: K, value: V },
+}
+
 /// A very mediocre hashmap
 ///
 /// # Examples
@@ -23,32 +34,72 @@ mod test;
 /// assert_eq!(map.get(&"tk2"), Some(&"tv2"));
 /// ```
 #[derive(Debug, Clone)]
-pub struct MediocreMap<K, V> {
-    lookup: Vec<Option<Vec<(K, Box<V>)>>>,
+pub struct MediocreMap<K, V, S = RandomState> {
+    lookup: Vec<Slot<K, V>>,
     count: usize,
+    hasher: S,
+}
+
+/// The error returned by [`MediocreMap::try_reserve`] when growth cannot be
+/// satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity is larger than can be represented.
+    CapacityOverflow,
+    /// The allocator could not hand back the requested amount of memory.
+    AllocError,
 }
 
-impl<K, V> MediocreMap<K, V> {
-    fn hash(input: impl Hash) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        input.hash(&mut hasher);
-        hasher.finish()
+impl std::fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            TryReserveError::CapacityOverflow => "requested capacity overflows the address space",
+            TryReserveError::AllocError => "the allocator failed to provide the requested memory",
+        };
+        f.write_str(msg)
     }
+}
+
+impl std::error::Error for TryReserveError {}
 
-    fn index(&self, input: impl Hash) -> usize {
-        (Self::hash(input) % self.lookup.len() as u64) as usize
+impl<K, V> MediocreMap<K, V, RandomState> {
+    /// Create a new Map with at least the given capacity.
+    ///
+    /// The bucket count is rounded up to a power of two (16 for a capacity of 0).
+    /// ```
+    /// let map = mediocremap::MediocreMap::<String, String>::new();
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::default())
+    }
+
+    /// Create a new map with a default capacity
+    pub fn new() -> Self {
+        Self::with_capacity(100)
+    }
+}
+
+impl<K, V, S> MediocreMap<K, V, S> {
+    fn hash(&self, input: impl Hash) -> u64
+    where
+        S: BuildHasher,
+    {
+        self.hasher.hash_one(input)
+    }
+
+    /// The probe distance of an element with `hash` living at `pos`: how far it
+    /// sits from its ideal slot, wrapping around the power-of-two store whose
+    /// bucket mask is `mask` (i.e. `len - 1`).
+    fn probe_distance(hash: u64, pos: usize, mask: usize) -> usize {
+        pos.wrapping_sub(hash as usize & mask) & mask
     }
 
     /// Create an iterator over all borrowed elements in the map
     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
-        self.lookup
-            .iter()
-            .filter_map(|x| match x {
-                Some(v) => Some(v.iter().collect::<Vec<_>>()),
-                None => None,
-            })
-            .flatten()
-            .map(|(k, v)| (k, v.as_ref()))
+        self.lookup.iter().filter_map(|slot| match slot {
+            Slot::Full { key, value, .. } => Some((key, value)),
+            Slot::Empty => None,
+        })
     }
 
     fn load_factor(&self) -> f64 {
@@ -57,39 +108,113 @@ impl<K, V> MediocreMap<K, V> {
 
     /// Create an iterator over all mutably borrowed elements in the map
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&mut K, &mut V)> {
-        self.lookup
-            .iter_mut()
-            .filter_map(|x| match x {
-                Some(v) => Some(v.iter_mut().collect::<Vec<_>>()),
-                None => None,
-            })
-            .flatten()
-            .map(|(k, v)| (k, v.as_mut()))
+        self.lookup.iter_mut().filter_map(|slot| match slot {
+            Slot::Full { key, value, .. } => Some((key, value)),
+            Slot::Empty => None,
+        })
     }
 
     /// Create an iterator over all elements in the map. This consumes the map
     pub fn into_iter(self) -> impl Iterator<Item = (K, V)> {
-        self.lookup
-            .into_iter()
-            .filter_map(|x| x)
-            .flatten()
-            .map(|(k, v)| (k, *v))
+        self.lookup.into_iter().filter_map(|slot| match slot {
+            Slot::Full { key, value, .. } => Some((key, value)),
+            Slot::Empty => None,
+        })
     }
 
-    /// Create a new Map with the given capaity.
-    /// ```
-    /// let map = mediocremap::MediocreMap::<String, String>::new();
-    /// ```
-    pub fn with_capacity(capacity: usize) -> Self {
+    /// Create a new map with a default capacity that hashes with `hasher`.
+    ///
+    /// This lets callers plug in a faster or deterministically seeded hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(100, hasher)
+    }
+
+    /// Create a new map with the given capacity that hashes with `hasher`.
+    ///
+    /// The real bucket count is always rounded up to a power of two so that the
+    /// slot index can be masked out of the hash instead of divided.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let buckets = Self::buckets_for(capacity);
         Self {
-            lookup: iter::repeat_with(|| None).take(capacity).collect(),
+            lookup: (0..buckets).map(|_| Slot::Empty).collect(),
             count: 0,
+            hasher,
         }
     }
 
-    /// Create a new map with a default capacity
-    pub fn new() -> Self {
-        Self::with_capacity(100)
+    /// Rounds a requested capacity up to the power-of-two bucket count actually
+    /// used by the store, with a non-zero minimum.
+    fn buckets_for(capacity: usize) -> usize {
+        if capacity == 0 {
+            16
+        } else {
+            capacity.next_power_of_two()
+        }
+    }
+
+    /// The power-of-two bucket count required to hold `entries` elements while
+    /// staying under [`TARGET_LOAD_FACTOR`], or an error if that overflows.
+    fn buckets_for_entries(entries: usize) -> Result<usize, TryReserveError> {
+        if entries == 0 {
+            return Ok(Self::buckets_for(0));
+        }
+
+        let min = (entries as f64 / TARGET_LOAD_FACTOR).ceil();
+        if !min.is_finite() || min >= usize::MAX as f64 {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+
+        (min as usize)
+            .checked_next_power_of_two()
+            .ok_or(TryReserveError::CapacityOverflow)
+    }
+
+    /// Tries to ensure the store can hold `additional` more entries without
+    /// reallocating, returning an error instead of aborting on failure.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError>
+    where
+        K: PartialEq<K>,
+    {
+        let required = self
+            .count
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        let target = Self::buckets_for_entries(required)?;
+
+        if target <= self.capacity() {
+            return Ok(());
+        }
+
+        self.try_resize(target)
+    }
+
+    /// Rebuilds the store with `buckets` slots using fallible allocation.
+    fn try_resize(&mut self, buckets: usize) -> Result<(), TryReserveError>
+    where
+        K: PartialEq<K>,
+    {
+        let bytes = buckets
+            .checked_mul(std::mem::size_of::<Slot<K, V>>())
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        if bytes > isize::MAX as usize {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+
+        let mut new_lookup: Vec<Slot<K, V>> = Vec::new();
+        new_lookup
+            .try_reserve_exact(buckets)
+            .map_err(|_| TryReserveError::AllocError)?;
+        new_lookup.extend((0..buckets).map(|_| Slot::Empty));
+
+        let old = std::mem::replace(&mut self.lookup, new_lookup);
+        self.count = 0;
+        // Slots already carry their hash, so no key is hashed a second time.
+        for slot in old {
+            if let Slot::Full { hash, key, value } = slot {
+                self.insert_hashed(hash, key, value);
+            }
+        }
+        Ok(())
     }
 
     /// Gets the number of items currently stored in the hashmap
@@ -105,48 +230,67 @@ impl<K, V> MediocreMap<K, V> {
     /// Resizes the underlying store to hold `new_size` elements without cloning the data inside the map.
     pub fn resize(&mut self, new_size: usize)
     where
-        K: Hash + PartialEq<K>,
+        K: PartialEq<K>,
     {
-        let mut new_self = Self::with_capacity(new_size);
+        let buckets = Self::buckets_for(new_size);
+        let old = std::mem::replace(&mut self.lookup, (0..buckets).map(|_| Slot::Empty).collect());
+        self.count = 0;
 
-        for bucket in self.lookup.iter_mut() {
-            if let Some(bucket) = bucket.take() {
-                for (key, value) in bucket {
-                    new_self.insert_static_boxed(key, value);
-                }
+        // The cached hash means rehoming every element is pure arithmetic.
+        for slot in old {
+            if let Slot::Full { hash, key, value } = slot {
+                self.insert_hashed(hash, key, value);
             }
         }
-
-        *self = new_self;
     }
 
-    /// Inserts boxed value WITHOUT resizing
-    fn insert_static_boxed(&mut self, key: K, value: Box<V>)
+    /// Inserts a pre-hashed entry into the store using Robin Hood linear
+    /// probing, WITHOUT growing it.
+    ///
+    /// Returns the final slot of the inserted (or updated) element and, when a
+    /// key was already present, its previous value.
+    fn insert_hashed(&mut self, hash: u64, key: K, value: V) -> (usize, Option<V>)
     where
-        K: Hash + PartialEq<K>,
+        K: PartialEq<K>,
     {
-        let index = self.index(&key);
+        let mask = self.lookup.len() - 1;
+        let mut pos = hash as usize & mask;
+        let mut dist = 0;
+        let (mut hash, mut key, mut value) = (hash, key, value);
+        // The slot the caller's element eventually comes to rest in.
+        let mut landed = None;
 
-        let bucket = self.lookup.get_mut(index).expect("insert broken");
+        loop {
+            match &mut self.lookup[pos] {
+                Slot::Empty => {
+                    self.lookup[pos] = Slot::Full { hash, key, value };
+                    self.count += 1;
+                    return (landed.unwrap_or(pos), None);
+                }
+                Slot::Full {
+                    hash: slot_hash,
+                    key: slot_key,
+                    value: slot_value,
+                } => {
+                    if *slot_hash == hash && *slot_key == key {
+                        return (pos, Some(std::mem::replace(slot_value, value)));
+                    }
 
-        let newly_inserted = if let Some(bucket) = bucket {
-            // Update the existing entry if the key already exists
-            let existing = bucket.iter().enumerate().find(|(_, (k, _))| k == &key);
-            if let Some((existing_idx, _)) = existing {
-                let entry = bucket.get_mut(existing_idx).expect("insert broken (again)");
-                *entry = (key, value);
-                false
-            } else {
-                bucket.push((key, value));
-                true
+                    // Rob from the rich: if the resident is closer to its ideal
+                    // slot than we are to ours, take its place and carry it on.
+                    let resident_dist = Self::probe_distance(*slot_hash, pos, mask);
+                    if resident_dist < dist {
+                        std::mem::swap(slot_hash, &mut hash);
+                        std::mem::swap(slot_key, &mut key);
+                        std::mem::swap(slot_value, &mut value);
+                        landed.get_or_insert(pos);
+                        dist = resident_dist;
+                    }
+                }
             }
-        } else {
-            *bucket = Some(vec![(key, value)]);
-            true
-        };
 
-        if newly_inserted {
-            self.count += 1;
+            pos = (pos + 1) & mask;
+            dist += 1;
         }
     }
 
@@ -156,51 +300,377 @@ impl<K, V> MediocreMap<K, V> {
     pub fn insert(&mut self, key: K, value: V)
     where
         K: Hash + PartialEq<K>,
+        S: BuildHasher,
+    {
+        if self.load_factor() >= TARGET_LOAD_FACTOR {
+            // Grow through the same sizing path as `try_reserve`, but keep the
+            // infallible contract by panicking on allocation failure.
+            let target = Self::buckets_for_entries(self.count + 1).expect("capacity overflow");
+            self.try_resize(target.max(self.capacity() * 2))
+                .expect("allocation failure while growing the map");
+        }
+
+        let hash = self.hash(&key);
+        self.insert_hashed(hash, key, value);
+    }
+
+    /// Gets the entry for `key`, allowing in-place modification or insertion
+    /// with only a single lookup.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut map = mediocremap::MediocreMap::new();
+    /// *map.entry("tk1").or_insert(0) += 1;
+    /// *map.entry("tk1").or_insert(0) += 1;
+    /// assert_eq!(map.get(&"tk1"), Some(&2));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S>
+    where
+        K: Hash + PartialEq<K>,
+        S: BuildHasher,
     {
+        // Grow up front using the same rule as `insert`, so a vacant insertion
+        // never has to re-probe an existing key into a freshly grown store.
         if self.load_factor() >= TARGET_LOAD_FACTOR {
             self.resize(self.capacity() * 2);
         }
 
-        self.insert_static_boxed(key, Box::new(value));
+        let hash = self.hash(&key);
+        let mask = self.lookup.len() - 1;
+        let mut pos = hash as usize & mask;
+        let mut dist = 0;
+
+        let found = loop {
+            match &self.lookup[pos] {
+                Slot::Empty => break None,
+                Slot::Full {
+                    hash: slot_hash,
+                    key: slot_key,
+                    ..
+                } => {
+                    if *slot_hash == hash && *slot_key == key {
+                        break Some(pos);
+                    }
+                    // A resident closer to home than our probe means the key
+                    // cannot be past here, so it is certainly vacant.
+                    if Self::probe_distance(*slot_hash, pos, mask) < dist {
+                        break None;
+                    }
+                }
+            }
+            pos = (pos + 1) & mask;
+            dist += 1;
+        };
+
+        match found {
+            Some(pos) => Entry::Occupied(OccupiedEntry { map: self, pos }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+            }),
+        }
     }
 
     /// Remove a given key. Returns None when the key was not present and it's value if it was.
-    pub fn remove(&mut self, key: &K) -> Option<V>
+    ///
+    /// The key may be any borrowed form of the map's key type, as long as it
+    /// hashes and compares equal to the owned key.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
     where
-        K: Hash + PartialEq<K>,
+        K: Borrow<Q>,
+        Q: Hash + PartialEq<Q> + ?Sized,
+        S: BuildHasher,
     {
-        let index = self.index(key);
-        let item = self.lookup.get_mut(index)?;
+        let pos = self.locate(key)?;
+        Some(self.remove_slot(pos).1)
+    }
 
-        if let Some(bucket) = item {
-            let (idx, _) = bucket.iter().enumerate().find(|(_, (k, _))| k == key)?;
-            let (_, removed_val) = bucket.remove(idx);
+    /// Removes the entry living at `pos`, returning its key and value.
+    ///
+    /// Uses backward-shift deletion: following elements are pulled back towards
+    /// their ideal slot until one is already home or a hole is reached, so the
+    /// store never needs tombstones.
+    fn remove_slot(&mut self, mut pos: usize) -> (K, V) {
+        let mask = self.lookup.len() - 1;
+        let (key, value) = match std::mem::replace(&mut self.lookup[pos], Slot::Empty) {
+            Slot::Full { key, value, .. } => (key, value),
+            Slot::Empty => unreachable!("removed an empty slot"),
+        };
 
-            self.count -= 1;
-            return Some(*removed_val);
-        } else {
-            None
+        loop {
+            let next = (pos + 1) & mask;
+            let shift_back = match &self.lookup[next] {
+                Slot::Full { hash, .. } => Self::probe_distance(*hash, next, mask) != 0,
+                Slot::Empty => false,
+            };
+            if !shift_back {
+                break;
+            }
+            self.lookup.swap(pos, next);
+            pos = next;
         }
+
+        self.count -= 1;
+        (key, value)
     }
 
     /// Get the value at the given key
-    pub fn get(&self, key: &K) -> Option<&V>
+    ///
+    /// The key may be any borrowed form of the map's key type, as long as it
+    /// hashes and compares equal to the owned key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Hash + PartialEq<K>,
+        K: Borrow<Q>,
+        Q: Hash + PartialEq<Q> + ?Sized,
+        S: BuildHasher,
     {
-        let index = self.index(key);
-        let item = self.lookup.get(index)?;
+        let pos = self.locate(key)?;
+        match &self.lookup[pos] {
+            Slot::Full { value, .. } => Some(value),
+            Slot::Empty => unreachable!("located slot was empty"),
+        }
+    }
 
-        if let Some(bucket) = item {
-            let (_, val) = &bucket.iter().find(|(k, _)| k == key)?;
-            Some(val)
-        } else {
-            None
+    /// Get a mutable reference to the value at the given key
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + PartialEq<Q> + ?Sized,
+        S: BuildHasher,
+    {
+        let pos = self.locate(key)?;
+        match &mut self.lookup[pos] {
+            Slot::Full { value, .. } => Some(value),
+            Slot::Empty => unreachable!("located slot was empty"),
+        }
+    }
+
+    /// Probes for `key`, returning the slot it occupies if present.
+    fn locate<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + PartialEq<Q> + ?Sized,
+        S: BuildHasher,
+    {
+        let hash = self.hash(key);
+        let mask = self.lookup.len() - 1;
+        let mut pos = hash as usize & mask;
+        let mut dist = 0;
+
+        loop {
+            match &self.lookup[pos] {
+                Slot::Empty => return None,
+                Slot::Full {
+                    hash: slot_hash,
+                    key: slot_key,
+                    ..
+                } => {
+                    if *slot_hash == hash && slot_key.borrow() == key {
+                        return Some(pos);
+                    }
+                    // Robin Hood invariant: a resident nearer home than our own
+                    // probe distance proves the key is absent.
+                    if Self::probe_distance(*slot_hash, pos, mask) < dist {
+                        return None;
+                    }
+                }
+            }
+            pos = (pos + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    /// Returns `true` when the map contains a value for the given key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + PartialEq<Q> + ?Sized,
+        S: BuildHasher,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Retains only the entries for which `f` returns `true`, dropping the rest.
+    ///
+    /// The cached slot hashes let the surviving entries be re-homed without
+    /// re-hashing their keys.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+        K: PartialEq<K>,
+    {
+        let len = self.lookup.len();
+        let old = std::mem::replace(&mut self.lookup, (0..len).map(|_| Slot::Empty).collect());
+        self.count = 0;
+
+        for slot in old {
+            if let Slot::Full {
+                hash,
+                key,
+                mut value,
+            } = slot
+            {
+                if f(&key, &mut value) {
+                    self.insert_hashed(hash, key, value);
+                }
+            }
+        }
+    }
+
+    /// Removes every entry from the map, yielding them as an iterator and
+    /// leaving the map empty.
+    pub fn drain(&mut self) -> impl Iterator<Item = (K, V)> {
+        let len = self.lookup.len();
+        let old = std::mem::replace(&mut self.lookup, (0..len).map(|_| Slot::Empty).collect());
+        self.count = 0;
+
+        old.into_iter().filter_map(|slot| match slot {
+            Slot::Full { key, value, .. } => Some((key, value)),
+            Slot::Empty => None,
+        })
+    }
+
+    /// Removes and yields the entries for which `pred` returns `true`, lazily.
+    ///
+    /// Entries are only removed as the returned iterator is consumed; dropping
+    /// it early leaves the unvisited entries in place.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, S, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            map: self,
+            pred,
+            pos: 0,
         }
     }
 }
 
-impl<K, V, const N: usize> From<[(K, V); N]> for MediocreMap<K, V>
+/// A view into a single entry of a [`MediocreMap`], obtained from
+/// [`MediocreMap::entry`].
+pub enum Entry<'a, K, V, S = RandomState> {
+    /// The key was already present in the map.
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    /// The key was not present in the map.
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// A view into an occupied entry. Part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut MediocreMap<K, V, S>,
+    pos: usize,
+}
+
+/// A view into a vacant entry. Part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut MediocreMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S> {
+    /// Ensures a value is in the entry by inserting `default` if it was vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V
+    where
+        K: PartialEq<K>,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default` if
+    /// it was vacant, and returns a mutable reference to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V
+    where
+        K: PartialEq<K>,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Runs `f` against the value if the entry is occupied, leaving a vacant
+    /// entry untouched.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    /// Gets a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut V {
+        match &mut self.map.lookup[self.pos] {
+            Slot::Full { value, .. } => value,
+            Slot::Empty => unreachable!("occupied entry over empty slot"),
+        }
+    }
+
+    /// Converts the entry into a mutable reference to the value, with the
+    /// lifetime of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        match &mut self.map.lookup[self.pos] {
+            Slot::Full { value, .. } => value,
+            Slot::Empty => unreachable!("occupied entry over empty slot"),
+        }
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+    /// Inserts `value` under the entry's key, reusing the hash computed when the
+    /// entry was created rather than re-hashing.
+    pub fn insert(self, value: V) -> &'a mut V
+    where
+        K: PartialEq<K>,
+    {
+        let (pos, _) = self.map.insert_hashed(self.hash, self.key, value);
+        match &mut self.map.lookup[pos] {
+            Slot::Full { value, .. } => value,
+            Slot::Empty => unreachable!("vacant insert left slot empty"),
+        }
+    }
+}
+
+/// A lazy draining filter over a [`MediocreMap`], returned by
+/// [`MediocreMap::extract_if`].
+pub struct ExtractIf<'a, K, V, S, F> {
+    map: &'a mut MediocreMap<K, V, S>,
+    pred: F,
+    pos: usize,
+}
+
+impl<K, V, S, F> Iterator for ExtractIf<'_, K, V, S, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.map.lookup.len() {
+            let matched = match &mut self.map.lookup[self.pos] {
+                Slot::Full { key, value, .. } => (self.pred)(key, value),
+                Slot::Empty => false,
+            };
+
+            if matched {
+                // The backward shift pulls a later element into this slot, so we
+                // stay put and re-examine the same position next time.
+                return Some(self.map.remove_slot(self.pos));
+            }
+            self.pos += 1;
+        }
+        None
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for MediocreMap<K, V, RandomState>
 where
     K: Hash + PartialEq<K>,
 {
@@ -213,34 +683,33 @@ where
     /// ```
     fn from(value: [(K, V); N]) -> Self {
         let len = value.len();
-        value
-            .into_iter()
-            .fold(MediocreMap::with_capacity(len), |mut state, x| {
-                state.insert(x.0, x.1);
-                state
-            })
+        value.into_iter().fold(Self::with_capacity(len), |mut state, x| {
+            state.insert(x.0, x.1);
+            state
+        })
     }
 }
 
-impl<K, V> FromIterator<(K, V)> for MediocreMap<K, V>
+impl<K, V> FromIterator<(K, V)> for MediocreMap<K, V, RandomState>
 where
     K: Hash + PartialEq<K>,
 {
     fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
-        iter.into_iter()
-            .fold(MediocreMap::with_capacity(100), |mut state, (k, v)| {
-                state.insert(k, v);
-                state
-            })
+        iter.into_iter().fold(Self::with_capacity(100), |mut state, (k, v)| {
+            state.insert(k, v);
+            state
+        })
     }
 }
 
-impl<K, V> std::ops::Index<K> for MediocreMap<K, V>
+impl<K, Q, V, S> std::ops::Index<&Q> for MediocreMap<K, V, S>
 where
-    K: Hash + PartialEq<K>,
+    K: Borrow<Q>,
+    Q: Hash + PartialEq<Q> + ?Sized,
+    S: BuildHasher,
 {
     type Output = V;
-    fn index(&self, index: K) -> &Self::Output {
-        return self.get(&index).unwrap();
+    fn index(&self, index: &Q) -> &Self::Output {
+        return self.get(index).unwrap();
     }
 }
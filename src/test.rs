@@ -0,0 +1,164 @@
+use crate::MediocreMap;
+use std::hash::{BuildHasher, Hasher};
+
+/// A hasher whose output for a `u64` key is simply the key itself, so tests
+/// can place entries in predictable slots and force probe clusters.
+#[derive(Default, Clone)]
+struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.wrapping_shl(8).wrapping_add(b as u64);
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
+
+#[derive(Default, Clone)]
+struct IdentityBuild;
+
+impl BuildHasher for IdentityBuild {
+    type Hasher = IdentityHasher;
+    fn build_hasher(&self) -> IdentityHasher {
+        IdentityHasher(0)
+    }
+}
+
+#[test]
+fn insert_get_remove_roundtrip() {
+    let mut map = MediocreMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get("a"), Some(&1));
+    assert_eq!(map.get("b"), Some(&2));
+    assert_eq!(map.get("c"), Some(&3));
+
+    assert_eq!(map.remove("b"), Some(2));
+    assert_eq!(map.remove("b"), None);
+    assert_eq!(map.get("b"), None);
+    assert_eq!(map.get("a"), Some(&1));
+    assert_eq!(map.get("c"), Some(&3));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn insert_overwrites_existing_key() {
+    let mut map = MediocreMap::new();
+    map.insert("k", 1);
+    map.insert("k", 9);
+
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get("k"), Some(&9));
+}
+
+#[test]
+fn resize_rehashes_all_entries() {
+    let mut map = MediocreMap::<u64, u64>::with_capacity(1);
+    for i in 0..1000 {
+        map.insert(i, i * 2);
+    }
+
+    assert_eq!(map.len(), 1000);
+    assert!(map.capacity() >= 1000);
+    assert!(map.capacity().is_power_of_two());
+    for i in 0..1000 {
+        assert_eq!(map.get(&i), Some(&(i * 2)), "lost {i} across resizes");
+    }
+}
+
+#[test]
+fn backward_shift_keeps_cluster_reachable() {
+    // Keys 0, 8, 16, 24 all hash to slot 0 in an 8-bucket store, forming a
+    // contiguous probe cluster across slots 0..=3.
+    let mut map = MediocreMap::with_capacity_and_hasher(8, IdentityBuild);
+    for k in [0u64, 8, 16, 24] {
+        map.insert(k, k);
+    }
+    assert_eq!(map.capacity(), 8);
+
+    // Removing from the middle of the cluster must shift the tail back so the
+    // later keys are still found.
+    assert_eq!(map.remove(&8), Some(8));
+    assert_eq!(map.get(&8), None);
+    assert_eq!(map.get(&0), Some(&0));
+    assert_eq!(map.get(&16), Some(&16));
+    assert_eq!(map.get(&24), Some(&24));
+    assert_eq!(map.len(), 3);
+}
+
+#[test]
+fn remove_and_extract_if_handle_wrap_around() {
+    // Keys 7, 15, 23 hash to slot 7 and probe forward, wrapping past the end
+    // of the 8-bucket store into slots 0 and 1.
+    let mut map = MediocreMap::with_capacity_and_hasher(8, IdentityBuild);
+    for k in [7u64, 15, 23] {
+        map.insert(k, k);
+    }
+
+    assert_eq!(map.remove(&15), Some(15));
+    assert_eq!(map.get(&7), Some(&7));
+    assert_eq!(map.get(&23), Some(&23));
+
+    map.insert(15, 15);
+    let mut extracted: Vec<(u64, u64)> = map.extract_if(|_, v| *v % 2 == 1).collect();
+    extracted.sort_unstable();
+    assert_eq!(extracted, vec![(7, 7), (15, 15), (23, 23)]);
+    assert_eq!(map.len(), 0);
+}
+
+#[test]
+fn retain_and_drain() {
+    let mut map = MediocreMap::<u64, u64>::with_capacity(4);
+    for i in 0..20 {
+        map.insert(i, i);
+    }
+
+    map.retain(|_, v| *v % 2 == 0);
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.get(&4), Some(&4));
+    assert_eq!(map.get(&5), None);
+
+    let mut drained: Vec<(u64, u64)> = map.drain().collect();
+    drained.sort_unstable();
+    assert_eq!(drained.len(), 10);
+    assert_eq!(drained[0], (0, 0));
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.get(&0), None);
+}
+
+#[test]
+fn entry_inserts_and_modifies() {
+    let mut map = MediocreMap::new();
+    *map.entry("a").or_insert(0) += 1;
+    *map.entry("a").or_insert(0) += 1;
+    map.entry("b").or_insert_with(|| 10);
+    map.entry("b").and_modify(|v| *v += 5).or_insert(0);
+
+    assert_eq!(map.get("a"), Some(&2));
+    assert_eq!(map.get("b"), Some(&15));
+}
+
+#[test]
+fn try_reserve_grows_for_additional_entries() {
+    let mut map = MediocreMap::<u64, u64>::with_capacity(4);
+    map.try_reserve(1000).expect("try_reserve should succeed");
+    assert!(map.capacity() >= (1000f64 / crate::TARGET_LOAD_FACTOR) as usize);
+
+    for i in 0..700 {
+        map.insert(i, i);
+    }
+    for i in 0..700 {
+        assert_eq!(map.get(&i), Some(&i));
+    }
+}